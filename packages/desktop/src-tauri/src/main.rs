@@ -1,11 +1,141 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use futures_util::StreamExt;
+use portable_pty::{native_pty_system, Child as PtyChild, CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
 use std::process::{Command, Child, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
+use tauri::{AppHandle, Manager, Window};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Maximum number of server log lines retained in the in-memory ring buffer.
+const MAX_LOG_LINES: usize = 1000;
+
+/// How often the supervisor polls the server's liveness.
+const SUPERVISOR_INTERVAL_SECS: u64 = 5;
+
+/// Default for `AppConfig::max_restart_attempts`, and the fallback used when
+/// deserializing a config file saved before that field existed.
+const DEFAULT_MAX_RESTART_ATTEMPTS: u32 = 3;
+
+/// Name of the persisted settings file inside the app's config directory.
+const CONFIG_FILE_NAME: &str = "config.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppConfig {
+    host: String,
+    port: u16,
+    launch_command: String,
+    launch_args: Vec<String>,
+    api_token: Option<String>,
+    #[serde(default = "default_max_restart_attempts")]
+    max_restart_attempts: u32,
+}
+
+fn default_max_restart_attempts() -> u32 {
+    DEFAULT_MAX_RESTART_ATTEMPTS
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            host: "127.0.0.1".to_string(),
+            port: 38080,
+            launch_command: "npm".to_string(),
+            launch_args: vec![
+                "start".to_string(),
+                "--".to_string(),
+                "--server-only".to_string(),
+                "--no_ui_output".to_string(),
+            ],
+            api_token: None,
+            max_restart_attempts: DEFAULT_MAX_RESTART_ATTEMPTS,
+        }
+    }
+}
+
+impl AppConfig {
+    fn base_url(&self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
+}
+
+fn config_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path_resolver()
+        .app_config_dir()
+        .ok_or("Failed to resolve app config directory")?;
+    fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    Ok(config_dir.join(CONFIG_FILE_NAME))
+}
+
+fn load_config(app_handle: &AppHandle) -> AppConfig {
+    config_file_path(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Persists the config, including the bearer `api_token`, so the file must
+/// not be left world/group readable.
+fn save_config(app_handle: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    let path = config_file_path(app_handle)?;
+    let contents = serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    // Create the file with the restrictive mode already in place rather than
+    // writing with default permissions and chmod'ing afterward, since the
+    // file holds the plaintext bearer `api_token` and must never be briefly
+    // world/group-readable.
+    #[cfg(unix)]
+    {
+        use std::fs::OpenOptions;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| format!("Failed to open config file: {}", e))?;
+        file.write_all(contents.as_bytes()).map_err(|e| format!("Failed to write config file: {}", e))?;
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(&path, contents).map_err(|e| format!("Failed to write config file: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_config(state: tauri::State<'_, Arc<Mutex<AppConfig>>>) -> Result<AppConfig, String> {
+    let config = state.lock().map_err(|e| format!("Failed to lock config: {}", e))?;
+    Ok(config.clone())
+}
+
+#[tauri::command]
+async fn set_config(
+    app_handle: AppHandle,
+    state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+    config: AppConfig,
+) -> Result<(), String> {
+    {
+        let mut current = state.lock().map_err(|e| format!("Failed to lock config: {}", e))?;
+        *current = config.clone();
+    }
+    save_config(&app_handle, &config)
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ServerStatus {
@@ -20,88 +150,172 @@ struct ChatMessage {
     tool_calls: Option<Vec<ToolCall>>,
 }
 
+/// `ToolCall.name` used to request an interactive shell command; routed
+/// through the PTY subsystem by `execute_tool_calls` instead of merely being
+/// surfaced to the frontend.
+const SHELL_TOOL_NAME: &str = "shell";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ToolCall {
     name: String,
     arguments: serde_json::Value,
+    /// Id of the PTY session this call was executed in, if it was a
+    /// `SHELL_TOOL_NAME` call and the PTY subsystem successfully launched it.
+    #[serde(default)]
+    pty_session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServerLogLine {
+    level: String,
+    line: String,
 }
 
 struct ServerState {
-    process: Option<Child>,
-    port: u16,
+    process: AsyncMutex<Option<Child>>,
+    running: AtomicBool,
+    pid: AtomicU32,
+    logs: Mutex<VecDeque<ServerLogLine>>,
+    restart_attempts: AtomicU32,
+}
+
+/// Resolves the directory the CLI server should be launched from.
+fn kolosal_path() -> Result<String, String> {
+    std::env::current_dir()
+        .map_err(|e| format!("Failed to get current directory: {}", e))?
+        .parent()
+        .and_then(|p| p.to_str())
+        .ok_or_else(|| "Failed to find kolosal-code directory".to_string())
+        .map(|p| p.to_string())
+}
+
+/// Spawns the CLI server as a child process with piped stdout/stderr, using
+/// the configured launch command/args and threading the configured port
+/// through as `--api-port` instead of a literal.
+fn spawn_server_process(kolosal_path: &str, config: &AppConfig) -> Result<Child, String> {
+    Command::new(&config.launch_command)
+        .args(&config.launch_args)
+        .args(["--api-port", &config.port.to_string()])
+        .current_dir(kolosal_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start server: {}", e))
+}
+
+/// Reads `reader` line-by-line, forwarding each line to the frontend as a
+/// `server-log` event and appending it to the bounded `logs` ring buffer.
+fn spawn_log_forwarder<R>(reader: R, level: &'static str, window: Window, state: Arc<ServerState>)
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let buf_reader = BufReader::new(reader);
+        for line in buf_reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            let log_line = ServerLogLine {
+                level: level.to_string(),
+                line,
+            };
+
+            if let Ok(mut logs) = state.logs.lock() {
+                logs.push_back(log_line.clone());
+                while logs.len() > MAX_LOG_LINES {
+                    logs.pop_front();
+                }
+            }
+
+            let _ = window.emit("server-log", &log_line);
+        }
+    });
+}
+
+#[tauri::command]
+async fn get_server_logs(state: tauri::State<'_, Arc<ServerState>>) -> Result<Vec<ServerLogLine>, String> {
+    let logs = state.logs.lock().map_err(|e| format!("Failed to lock logs: {}", e))?;
+    Ok(logs.iter().cloned().collect())
 }
 
 #[tauri::command]
-async fn start_server(state: tauri::State<'_, Arc<Mutex<ServerState>>>) -> Result<String, String> {
+async fn start_server(
+    window: Window,
+    state: tauri::State<'_, Arc<ServerState>>,
+    config_state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+) -> Result<String, String> {
     // Check if server is already running
     {
-        let server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-        if server_state.process.is_some() {
+        let process = state.process.lock().await;
+        if process.is_some() {
             return Err("Server is already running".to_string());
         }
     }
 
+    let config = config_state.lock().map_err(|e| format!("Failed to lock config: {}", e))?.clone();
+
     // Find the kolosal-code directory
-    let kolosal_path = std::env::current_dir()
-        .map_err(|e| format!("Failed to get current directory: {}", e))?
-        .parent()
-        .and_then(|p| p.to_str())
-        .ok_or("Failed to find kolosal-code directory")?
-        .to_string();
+    let path = kolosal_path()?;
 
-    println!("Starting Kolosal server in: {}", kolosal_path);
+    println!("Starting Kolosal server in: {}", path);
 
     // Start the CLI server
-    let child = Command::new("npm")
-        .args(&[
-            "start",
-            "--",
-            "--server-only",
-            "--api-port",
-            "38080",
-            "--no_ui_output"
-        ])
-        .current_dir(&kolosal_path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to start server: {}", e))?;
+    let mut child = spawn_server_process(&path, &config)?;
 
     let pid = child.id();
-    
+
+    // Forward the child's stdout/stderr to the frontend as they arrive, since
+    // the handles are dropped otherwise and all server diagnostics are lost.
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
     // Store the process in the state
     {
-        let mut server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-        server_state.process = Some(child);
+        let mut process = state.process.lock().await;
+        *process = Some(child);
+    }
+    state.pid.store(pid, Ordering::SeqCst);
+
+    if let Some(stdout) = stdout {
+        spawn_log_forwarder(stdout, "stdout", window.clone(), state.inner().clone());
+    }
+    if let Some(stderr) = stderr {
+        spawn_log_forwarder(stderr, "stderr", window.clone(), state.inner().clone());
     }
 
-    // Wait a moment for server to start
-    thread::sleep(Duration::from_secs(3));
+    // Wait a moment for server to start without blocking the async executor.
+    tokio::time::sleep(Duration::from_secs(3)).await;
 
     // Check if server is responsive
-    if check_server_health().await {
+    if check_server_health(&config).await {
+        state.running.store(true, Ordering::SeqCst);
+        state.restart_attempts.store(0, Ordering::SeqCst);
         Ok(format!("Server started successfully (PID: {})", pid))
     } else {
         // If server is not responsive, kill it and return error
         {
-            let mut server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-            if let Some(mut process) = server_state.process.take() {
+            let mut process = state.process.lock().await;
+            if let Some(mut process) = process.take() {
                 let _ = process.kill();
             }
         }
+        state.running.store(false, Ordering::SeqCst);
         Err("Server failed to start properly".to_string())
     }
 }
 
 #[tauri::command]
-async fn stop_server(state: tauri::State<'_, Arc<Mutex<ServerState>>>) -> Result<String, String> {
-    let mut server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-    
-    if let Some(mut process) = server_state.process.take() {
-        match process.kill() {
+async fn stop_server(state: tauri::State<'_, Arc<ServerState>>) -> Result<String, String> {
+    let mut process = state.process.lock().await;
+
+    if let Some(mut child) = process.take() {
+        match child.kill() {
             Ok(_) => {
                 // Wait for process to actually stop
-                let _ = process.wait();
+                let _ = child.wait();
+                state.running.store(false, Ordering::SeqCst);
                 Ok("Server stopped successfully".to_string())
             }
             Err(e) => Err(format!("Failed to stop server: {}", e)),
@@ -112,42 +326,69 @@ async fn stop_server(state: tauri::State<'_, Arc<Mutex<ServerState>>>) -> Result
 }
 
 #[tauri::command]
-async fn check_server_status(state: tauri::State<'_, Arc<Mutex<ServerState>>>) -> Result<ServerStatus, String> {
-    let has_process = {
-        let server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-        server_state.process.is_some()
-    };
+async fn check_server_status(
+    state: tauri::State<'_, Arc<ServerState>>,
+    config_state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+) -> Result<ServerStatus, String> {
+    let has_process = state.process.lock().await.is_some();
+
+    let config = config_state.lock().map_err(|e| format!("Failed to lock config: {}", e))?.clone();
 
+    // Always hit the health endpoint when a process is present - the cached
+    // flag below is for detecting transitions (e.g. for the log ring
+    // buffer), not for deciding whether to trust a fresh check.
     let running = if has_process {
-        check_server_health().await
+        check_server_health(&config).await
     } else {
         false
     };
+    let was_running = state.running.swap(running, Ordering::SeqCst);
+    if was_running != running {
+        if let Ok(mut logs) = state.logs.lock() {
+            logs.push_back(ServerLogLine {
+                level: "status".to_string(),
+                line: if running {
+                    "Server is responding again".to_string()
+                } else {
+                    "Server stopped responding".to_string()
+                },
+            });
+            while logs.len() > MAX_LOG_LINES {
+                logs.pop_front();
+            }
+        }
+    }
 
-    let pid = {
-        let server_state = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-        server_state.process.as_ref().map(|p| p.id())
-    };
+    let pid = has_process.then(|| state.pid.load(Ordering::SeqCst));
 
     Ok(ServerStatus {
         running,
-        port: 38080,
+        port: config.port,
         pid,
     })
 }
 
 #[tauri::command]
-async fn send_message(message: String) -> Result<ChatMessage, String> {
+async fn send_message(
+    window: Window,
+    config_state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+    pty_state: tauri::State<'_, Arc<PtyManager>>,
+    message: String,
+) -> Result<ChatMessage, String> {
+    let config = config_state.lock().map_err(|e| format!("Failed to lock config: {}", e))?.clone();
     let client = reqwest::Client::new();
-    
+
     let request_body = serde_json::json!({
         "input": message,
         "stream": false
     });
 
-    let response = client
-        .post("http://127.0.0.1:38080/v1/generate")
-        .json(&request_body)
+    let mut request = client.post(format!("{}/v1/generate", config.base_url())).json(&request_body);
+    if let Some(token) = &config.api_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
         .send()
         .await
         .map_err(|e| format!("Failed to send request: {}", e))?;
@@ -168,10 +409,10 @@ async fn send_message(message: String) -> Result<ChatMessage, String> {
         .to_string();
 
     // Extract tool calls if present
-    let tool_calls = response_json
+    let mut tool_calls = response_json
         .get("messages")
         .and_then(|messages| messages.as_array())
-        .and_then(|arr| {
+        .map(|arr| {
             let mut calls = Vec::new();
             for msg in arr {
                 if let Some(_tool_call) = msg.get("type").and_then(|t| t.as_str()).filter(|&t| t == "tool_call") {
@@ -182,50 +423,458 @@ async fn send_message(message: String) -> Result<ChatMessage, String> {
                         calls.push(ToolCall {
                             name: name.to_string(),
                             arguments: args.clone(),
+                            pty_session_id: None,
                         });
                     }
                 }
             }
-            if !calls.is_empty() {
-                Some(calls)
-            } else {
-                None
-            }
-        });
+            calls
+        })
+        .unwrap_or_default();
+
+    execute_tool_calls(&window, pty_state.inner(), &mut tool_calls);
 
     Ok(ChatMessage {
         content,
-        tool_calls,
+        tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
     })
 }
 
-async fn check_server_health() -> bool {
+/// Finds the first occurrence of `needle` in `haystack`, returning the index
+/// of its start.
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[tauri::command]
+async fn send_message_streaming(
+    window: Window,
+    config_state: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+    pty_state: tauri::State<'_, Arc<PtyManager>>,
+    message: String,
+) -> Result<(), String> {
+    let config = config_state.lock().map_err(|e| format!("Failed to lock config: {}", e))?.clone();
     let client = reqwest::Client::new();
-    
-    match client
-        .get("http://127.0.0.1:38080/healthz")
-        .timeout(Duration::from_secs(2))
+
+    let request_body = serde_json::json!({
+        "input": message,
+        "stream": true
+    });
+
+    let mut request = client.post(format!("{}/v1/generate", config.base_url())).json(&request_body);
+    if let Some(token) = &config.api_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
         .send()
         .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Server returned error: {}", response.status()));
+    }
+
+    let mut byte_stream = response.bytes_stream();
+    // Raw bytes, not a String: network chunk boundaries don't respect UTF-8
+    // codepoint boundaries, so a multi-byte character can arrive split across
+    // two polls. Frames are only decoded once a full "\n\n"-delimited frame
+    // has been buffered.
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut content = String::new();
+    let mut tool_calls: Vec<ToolCall> = Vec::new();
+
+    while let Some(chunk) = byte_stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to read stream: {}", e))?;
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(pos) = find_subslice(&buffer, b"\n\n") {
+            let frame = String::from_utf8_lossy(&buffer[..pos]).trim().to_string();
+            buffer.drain(..pos + 2);
+
+            let data = frame.strip_prefix("data: ").unwrap_or(&frame).trim();
+            if data.is_empty() || data == "[DONE]" {
+                continue;
+            }
+
+            let frame_json: serde_json::Value = match serde_json::from_str(data) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            if let Some(delta) = frame_json.get("delta").and_then(|d| d.as_str()) {
+                content.push_str(delta);
+                window
+                    .emit("chat-token", delta)
+                    .map_err(|e| format!("Failed to emit chat-token event: {}", e))?;
+            }
+
+            if frame_json.get("type").and_then(|t| t.as_str()) == Some("tool_call") {
+                if let (Some(name), Some(args)) = (
+                    frame_json.get("name").and_then(|n| n.as_str()),
+                    frame_json.get("arguments"),
+                ) {
+                    tool_calls.push(ToolCall {
+                        name: name.to_string(),
+                        arguments: args.clone(),
+                        pty_session_id: None,
+                    });
+                }
+            }
+        }
+    }
+
+    execute_tool_calls(&window, pty_state.inner(), &mut tool_calls);
+
+    window
+        .emit(
+            "chat-done",
+            ChatMessage {
+                content,
+                tool_calls: if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                },
+            },
+        )
+        .map_err(|e| format!("Failed to emit chat-done event: {}", e))?;
+
+    Ok(())
+}
+
+/// Attempts to relaunch the server after an unexpected death, backing off
+/// exponentially (1s, 2s, 4s, capped) and giving up after
+/// `config.max_restart_attempts` consecutive failures.
+fn restart_server(window: &Window, state: &Arc<ServerState>, config: &AppConfig) {
+    let attempt = {
+        let prev = state.restart_attempts.load(Ordering::SeqCst);
+        if prev >= config.max_restart_attempts {
+            return;
+        }
+        state.restart_attempts.fetch_add(1, Ordering::SeqCst) + 1
+    };
+
+    let backoff_secs = 1u64 << (attempt - 1).min(2); // 1s, 2s, 4s capped
+    thread::sleep(Duration::from_secs(backoff_secs));
+
+    let path = match kolosal_path() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    match spawn_server_process(&path, config) {
+        Ok(mut child) => {
+            let pid = child.id();
+            let stdout = child.stdout.take();
+            let stderr = child.stderr.take();
+
+            *state.process.blocking_lock() = Some(child);
+            state.pid.store(pid, Ordering::SeqCst);
+
+            if let Some(stdout) = stdout {
+                spawn_log_forwarder(stdout, "stdout", window.clone(), state.clone());
+            }
+            if let Some(stderr) = stderr {
+                spawn_log_forwarder(stderr, "stderr", window.clone(), state.clone());
+            }
+
+            let _ = window.emit("server-restarted", attempt);
+        }
+        Err(e) => {
+            let _ = window.emit("server-down", format!("Auto-restart attempt {} failed: {}", attempt, e));
+            // The supervisor skips an empty process slot, so retry here until
+            // the attempt limit is reached.
+            restart_server(window, state, config);
+        }
+    }
+}
+
+/// Background supervisor that periodically checks whether the server
+/// process is still alive and responsive, emitting `server-down` and
+/// attempting an auto-restart when it isn't.
+fn spawn_supervisor(window: Window, state: Arc<ServerState>, config_state: Arc<Mutex<AppConfig>>) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(SUPERVISOR_INTERVAL_SECS));
+
+        let config = match config_state.lock() {
+            Ok(c) => c.clone(),
+            Err(_) => continue,
+        };
+
+        let exited_unexpectedly = {
+            let mut process = state.process.blocking_lock();
+            match process.as_mut() {
+                Some(child) => matches!(child.try_wait(), Ok(Some(_)) | Err(_)),
+                None => false,
+            }
+        };
+
+        if exited_unexpectedly {
+            *state.process.blocking_lock() = None;
+            state.running.store(false, Ordering::SeqCst);
+            let _ = window.emit("server-down", "Server process exited unexpectedly");
+            restart_server(&window, &state, &config);
+            continue;
+        }
+
+        let has_process = state.process.blocking_lock().is_some();
+        if !has_process {
+            continue;
+        }
+
+        if tauri::async_runtime::block_on(check_server_health(&config)) {
+            state.running.store(true, Ordering::SeqCst);
+            state.restart_attempts.store(0, Ordering::SeqCst);
+        } else {
+            {
+                let mut process = state.process.blocking_lock();
+                if let Some(mut child) = process.take() {
+                    let _ = child.kill();
+                }
+            }
+            state.running.store(false, Ordering::SeqCst);
+            let _ = window.emit("server-down", "Server stopped responding to health checks");
+            restart_server(&window, &state, &config);
+        }
+    });
+}
+
+struct PtySession {
+    writer: Box<dyn Write + Send>,
+    master: Box<dyn MasterPty + Send>,
+    child: Box<dyn PtyChild + Send + Sync>,
+}
+
+struct PtyManager {
+    sessions: Mutex<HashMap<String, PtySession>>,
+    next_id: AtomicU64,
+}
+
+/// Resolves the shell to spawn inside a new PTY session.
+fn default_shell() -> String {
+    if cfg!(windows) {
+        std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
+    } else {
+        std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
+    }
+}
+
+/// Opens a new pseudo-terminal running the user's shell and streams its
+/// output to the frontend as `pty-output-{session_id}` events, so model tool
+/// calls that need an interactive shell have somewhere to run.
+#[tauri::command]
+async fn pty_open(window: Window, state: tauri::State<'_, Arc<PtyManager>>, cols: u16, rows: u16) -> Result<String, String> {
+    open_pty_session(&window, state.inner(), cols, rows)
+}
+
+/// Core of `pty_open`, taking a plain `&Arc<PtyManager>` rather than
+/// `tauri::State` so it can also be driven directly by `execute_tool_calls`
+/// for shell-type tool calls, not just the `pty_open` command.
+fn open_pty_session(window: &Window, manager: &Arc<PtyManager>, cols: u16, rows: u16) -> Result<String, String> {
+    let pty_system = native_pty_system();
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open pty: {}", e))?;
+
+    let child = pair
+        .slave
+        .spawn_command(CommandBuilder::new(default_shell()))
+        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to clone pty reader: {}", e))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to take pty writer: {}", e))?;
+
+    let session_id = manager.next_id.fetch_add(1, Ordering::SeqCst).to_string();
+
     {
+        let mut sessions = manager.sessions.lock().map_err(|e| format!("Failed to lock pty sessions: {}", e))?;
+        sessions.insert(
+            session_id.clone(),
+            PtySession {
+                writer,
+                master: pair.master,
+                child,
+            },
+        );
+    }
+
+    let output_window = window.clone();
+    let output_session_id = session_id.clone();
+    let output_manager = manager.clone();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let chunk = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let _ = output_window.emit(&format!("pty-output-{}", output_session_id), chunk);
+                }
+            }
+        }
+
+        // The shell exited on its own (or the pty broke) - drop the session
+        // so pty_write/pty_resize stop "succeeding" against a dead session.
+        if let Ok(mut sessions) = output_manager.sessions.lock() {
+            sessions.remove(&output_session_id);
+        }
+
+        let _ = output_window.emit(&format!("pty-exit-{}", output_session_id), ());
+    });
+
+    Ok(session_id)
+}
+
+#[tauri::command]
+async fn pty_write(state: tauri::State<'_, Arc<PtyManager>>, session_id: String, data: String) -> Result<(), String> {
+    write_to_pty(state.inner(), &session_id, &data)
+}
+
+/// Core of `pty_write`, taking a plain `&Arc<PtyManager>` so it can also be
+/// driven directly by `execute_tool_calls`.
+fn write_to_pty(manager: &Arc<PtyManager>, session_id: &str, data: &str) -> Result<(), String> {
+    let mut sessions = manager.sessions.lock().map_err(|e| format!("Failed to lock pty sessions: {}", e))?;
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or_else(|| format!("No pty session with id {}", session_id))?;
+    session
+        .writer
+        .write_all(data.as_bytes())
+        .map_err(|e| format!("Failed to write to pty: {}", e))?;
+    session.writer.flush().map_err(|e| format!("Failed to flush pty: {}", e))
+}
+
+#[tauri::command]
+async fn pty_resize(state: tauri::State<'_, Arc<PtyManager>>, session_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    let sessions = state.sessions.lock().map_err(|e| format!("Failed to lock pty sessions: {}", e))?;
+    let session = sessions
+        .get(&session_id)
+        .ok_or_else(|| format!("No pty session with id {}", session_id))?;
+    session
+        .master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to resize pty: {}", e))
+}
+
+#[tauri::command]
+async fn pty_kill(state: tauri::State<'_, Arc<PtyManager>>, session_id: String) -> Result<(), String> {
+    let mut sessions = state.sessions.lock().map_err(|e| format!("Failed to lock pty sessions: {}", e))?;
+    let mut session = sessions
+        .remove(&session_id)
+        .ok_or_else(|| format!("No pty session with id {}", session_id))?;
+    session.child.kill().map_err(|e| format!("Failed to kill pty session: {}", e))
+}
+
+/// Drives any `SHELL_TOOL_NAME` calls in `tool_calls` through the PTY
+/// subsystem: each one opens its own PTY session, writes its `command`
+/// argument followed by a newline, and records the session id back onto the
+/// call so the frontend can attach to its `pty-output-{id}` stream. Other
+/// tool names are left untouched - the frontend keeps handling those itself.
+fn execute_tool_calls(window: &Window, pty_manager: &Arc<PtyManager>, tool_calls: &mut [ToolCall]) {
+    for tool_call in tool_calls.iter_mut() {
+        if tool_call.name != SHELL_TOOL_NAME {
+            continue;
+        }
+
+        let command = match tool_call.arguments.get("command").and_then(|c| c.as_str()) {
+            Some(command) => command,
+            None => continue,
+        };
+
+        let session_id = match open_pty_session(window, pty_manager, 80, 24) {
+            Ok(id) => id,
+            Err(e) => {
+                let _ = window.emit("pty-error", format!("Failed to start shell tool call: {}", e));
+                continue;
+            }
+        };
+
+        if let Err(e) = write_to_pty(pty_manager, &session_id, &format!("{}\n", command)) {
+            let _ = window.emit("pty-error", format!("Failed to run shell tool call: {}", e));
+            continue;
+        }
+
+        tool_call.pty_session_id = Some(session_id);
+    }
+}
+
+async fn check_server_health(config: &AppConfig) -> bool {
+    let client = reqwest::Client::new();
+
+    let mut request = client
+        .get(format!("{}/healthz", config.base_url()))
+        .timeout(Duration::from_secs(2));
+    if let Some(token) = &config.api_token {
+        request = request.bearer_auth(token);
+    }
+
+    match request.send().await {
         Ok(response) => response.status().is_success(),
         Err(_) => false,
     }
 }
 
 fn main() {
-    let server_state = Arc::new(Mutex::new(ServerState {
-        process: None,
-        port: 38080,
-    }));
+    let server_state = Arc::new(ServerState {
+        process: AsyncMutex::new(None),
+        running: AtomicBool::new(false),
+        pid: AtomicU32::new(0),
+        logs: Mutex::new(VecDeque::new()),
+        restart_attempts: AtomicU32::new(0),
+    });
+
+    let pty_manager = Arc::new(PtyManager {
+        sessions: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    });
 
     tauri::Builder::default()
-        .manage(server_state)
+        .manage(server_state.clone())
+        .manage(pty_manager)
+        .setup(move |app| {
+            let window = app
+                .get_window("main")
+                .ok_or("Failed to find main window")?;
+
+            let config = load_config(&app.handle());
+            let config_state = Arc::new(Mutex::new(config));
+            app.manage(config_state.clone());
+
+            spawn_supervisor(window, server_state.clone(), config_state);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             start_server,
             stop_server,
             check_server_status,
-            send_message
+            send_message,
+            send_message_streaming,
+            get_server_logs,
+            pty_open,
+            pty_write,
+            pty_resize,
+            pty_kill,
+            get_config,
+            set_config
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");